@@ -345,11 +345,164 @@ struct ChatResponse {
     usage: Option<serde_json::Value>,
 }
 
-// Mock LLM client (replace with actual API calls)
-async fn call_llm_api(prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
-    // This would be your actual API call to OpenAI or Anthropic
-    // For demo purposes, we'll simulate it
-    
+// Error surface for the LLM client, so callers can match on the failure mode
+// instead of digging through a boxed `dyn Error`.
+#[derive(Debug)]
+enum LlmError {
+    Network(String),
+    Deserialization(String),
+    RateLimitExhausted { attempts: u32 },
+}
+
+impl std::fmt::Display for LlmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LlmError::Network(msg) => write!(f, "network error: {}", msg),
+            LlmError::Deserialization(msg) => write!(f, "failed to parse response: {}", msg),
+            LlmError::RateLimitExhausted { attempts } => {
+                write!(f, "gave up after {} attempt(s): still rate-limited or failing transiently", attempts)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LlmError {}
+
+// Blocking "send-and-confirm" half of the client: build the request, POST it,
+// and retry transient failures with backoff before handing back a response.
+trait SyncLlmClient {
+    fn endpoint(&self) -> &str;
+    fn send(&self, request: &ChatRequest) -> Result<ChatResponse, LlmError>;
+}
+
+// Non-blocking half: fire the request without waiting for the response body.
+trait AsyncLlmClient {
+    async fn send(&self, request: ChatRequest) -> Result<(), LlmError>;
+}
+
+// A client that can do both, and knows where it's talking to.
+trait LlmClient: SyncLlmClient + AsyncLlmClient {
+    fn base_url(&self) -> &str;
+}
+
+fn is_transient_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+struct HttpLlmClient {
+    blocking: reqwest::blocking::Client,
+    non_blocking: reqwest::Client,
+    base_url: String,
+    api_key_env: &'static str,
+    model_env: &'static str,
+    max_retries: u32,
+}
+
+impl HttpLlmClient {
+    fn new(base_url: impl Into<String>, api_key_env: &'static str, model_env: &'static str) -> Self {
+        HttpLlmClient {
+            blocking: reqwest::blocking::Client::new(),
+            non_blocking: reqwest::Client::new(),
+            base_url: base_url.into(),
+            api_key_env,
+            model_env,
+            max_retries: 4,
+        }
+    }
+
+    // Re-read the API key and model on every attempt so a mid-retry rotation
+    // (or env change) is picked up instead of reusing stale values.
+    fn current_api_key(&self) -> String {
+        std::env::var(self.api_key_env).unwrap_or_else(|_| "demo-key".to_string())
+    }
+
+    fn current_model(&self, requested: &str) -> String {
+        std::env::var(self.model_env).unwrap_or_else(|_| requested.to_string())
+    }
+}
+
+impl SyncLlmClient for HttpLlmClient {
+    fn endpoint(&self) -> &str {
+        "/v1/chat/completions"
+    }
+
+    fn send(&self, request: &ChatRequest) -> Result<ChatResponse, LlmError> {
+        let mut attempt = 0;
+        loop {
+            let api_key = self.current_api_key();
+            let model = self.current_model(&request.model);
+            let attempt_request = ChatRequest {
+                model,
+                messages: request.messages.iter().map(|m| ChatMessage {
+                    role: m.role.clone(),
+                    content: m.content.clone(),
+                }).collect(),
+                max_tokens: request.max_tokens,
+                temperature: request.temperature,
+            };
+
+            let outcome = self
+                .blocking
+                .post(format!("{}{}", self.base_url, self.endpoint()))
+                .header("Authorization", format!("Bearer {}", api_key))
+                .header("Content-Type", "application/json")
+                .json(&attempt_request)
+                .send();
+
+            let should_retry = match &outcome {
+                Ok(response) => is_transient_status(response.status()),
+                Err(e) => e.is_timeout() || e.is_connect(),
+            };
+
+            if !should_retry {
+                let response = outcome.map_err(|e| LlmError::Network(e.to_string()))?;
+                if !response.status().is_success() {
+                    return Err(LlmError::Network(format!("request failed: {}", response.status())));
+                }
+                return response
+                    .json::<ChatResponse>()
+                    .map_err(|e| LlmError::Deserialization(e.to_string()));
+            }
+
+            attempt += 1;
+            if attempt > self.max_retries {
+                return Err(LlmError::RateLimitExhausted { attempts: attempt });
+            }
+            let backoff_ms = 200u64 * 2u64.pow(attempt - 1);
+            thread::sleep(Duration::from_millis(backoff_ms));
+        }
+    }
+}
+
+impl AsyncLlmClient for HttpLlmClient {
+    async fn send(&self, request: ChatRequest) -> Result<(), LlmError> {
+        let api_key = self.current_api_key();
+        let model = self.current_model(&request.model);
+        let url = format!("{}{}", self.base_url, SyncLlmClient::endpoint(self));
+        let attempt_request = ChatRequest { model, ..request };
+
+        self.non_blocking
+            .post(url)
+            .header("Authorization", format!("Bearer {}", api_key))
+            .header("Content-Type", "application/json")
+            .json(&attempt_request)
+            .send()
+            .await
+            .map_err(|e| LlmError::Network(e.to_string()))?;
+        Ok(())
+    }
+}
+
+impl LlmClient for HttpLlmClient {
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+}
+
+// Helper function to interact with LLM
+async fn ask_llm_about_rust(topic: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let prompt = format!("Explain this Rust concept in simple terms: {}", topic);
+    let client = HttpLlmClient::new("https://api.openai.com", "OPENAI_API_KEY", "OPENAI_MODEL");
     let request = ChatRequest {
         model: "gpt-3.5-turbo".to_string(),
         messages: vec![
@@ -359,24 +512,453 @@ async fn call_llm_api(prompt: &str) -> Result<String, Box<dyn std::error::Error>
             },
             ChatMessage {
                 role: "user".to_string(),
-                content: prompt.to_string(),
+                content: prompt,
             },
         ],
         max_tokens: Some(150),
         temperature: Some(0.7),
     };
-    
-    // Simulate API call delay
-    sleep(Duration::from_millis(100)).await;
-    
-    // Mock response
-    Ok(format!("LLM Response to '{}': This is a simulated response about Rust concepts. In a real implementation, you would use reqwest to call the actual API.", prompt))
+
+    // `send` blocks on the socket, so hand it to a blocking-friendly thread
+    // rather than stalling the async executor.
+    let response = tokio::task::spawn_blocking(move || SyncLlmClient::send(&client, &request))
+        .await
+        .map_err(|e| LlmError::Network(e.to_string()))??;
+
+    response
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content)
+        .ok_or_else(|| LlmError::Deserialization("response had no choices".to_string()).into())
 }
 
-// Helper function to interact with LLM
-async fn ask_llm_about_rust(topic: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let prompt = format!("Explain this Rust concept in simple terms: {}", topic);
-    call_llm_api(&prompt).await
+// Fire-and-forget counterpart to `ask_llm_about_rust`: logs that a topic is
+// being explored without blocking on (or even reading) the LLM's reply,
+// exercising `AsyncLlmClient`'s non-blocking half.
+async fn notify_llm_about_rust(topic: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let client = HttpLlmClient::new("https://api.openai.com", "OPENAI_API_KEY", "OPENAI_MODEL");
+    let request = ChatRequest {
+        model: "gpt-3.5-turbo".to_string(),
+        messages: vec![ChatMessage {
+            role: "user".to_string(),
+            content: format!("Note: a user is exploring the Rust concept '{}'.", topic),
+        }],
+        max_tokens: Some(1),
+        temperature: Some(0.0),
+    };
+
+    AsyncLlmClient::send(&client, request).await?;
+    Ok(())
+}
+
+// ============================================================================
+// 14. STRING-TO-TYPED-VALUE CONVERSION SUBSYSTEM
+// ============================================================================
+
+// Names a conversion so it can be carried around as config (e.g. "this log
+// field is a timestamp") and applied later to raw strings/bytes.
+#[derive(Debug, Clone, PartialEq)]
+enum Conversion {
+    Bytes,
+    Integer,
+    Float,
+    Boolean,
+    Timestamp,
+    TimestampFmt(String),
+    TimestampTZFmt(String),
+}
+
+#[derive(Debug)]
+enum ConversionError {
+    UnknownConversion { name: String },
+    ParseFailure { input: String, reason: String },
+}
+
+impl std::fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversionError::UnknownConversion { name } => {
+                write!(f, "unknown conversion: '{}'", name)
+            }
+            ConversionError::ParseFailure { input, reason } => {
+                write!(f, "could not convert '{}': {}", input, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+// Parsed as `"<name>"` or `"<name>|<fmt>"` (only the two timestamp variants
+// take a format string).
+impl std::str::FromStr for Conversion {
+    type Err = ConversionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, fmt) = match s.split_once('|') {
+            Some((name, fmt)) => (name, Some(fmt)),
+            None => (s, None),
+        };
+
+        match (name.to_ascii_lowercase().as_str(), fmt) {
+            ("int", None) | ("integer", None) => Ok(Conversion::Integer),
+            ("float", None) => Ok(Conversion::Float),
+            ("bool", None) | ("boolean", None) => Ok(Conversion::Boolean),
+            ("string", None) | ("asis", None) | ("bytes", None) => Ok(Conversion::Bytes),
+            ("timestamp", None) => Ok(Conversion::Timestamp),
+            ("timestamp", Some(fmt)) if fmt.contains("%z") || fmt.contains("%Z") || fmt.contains("%:z") => {
+                Ok(Conversion::TimestampTZFmt(fmt.to_string()))
+            }
+            ("timestamp", Some(fmt)) => Ok(Conversion::TimestampFmt(fmt.to_string())),
+            _ => Err(ConversionError::UnknownConversion { name: s.to_string() }),
+        }
+    }
+}
+
+// The typed result of applying a `Conversion` to raw input.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Bytes(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Timestamp(i64), // Unix seconds, UTC
+}
+
+// A handful of formats common enough in logs/config to try without being told.
+const COMMON_TIMESTAMP_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d",
+];
+
+impl Conversion {
+    fn apply(&self, input: &str) -> Result<Value, ConversionError> {
+        let input = input.trim();
+        let fail = |reason: String| ConversionError::ParseFailure {
+            input: input.to_string(),
+            reason,
+        };
+
+        match self {
+            Conversion::Bytes => Ok(Value::Bytes(input.to_string())),
+            Conversion::Integer => input.parse::<i64>().map(Value::Integer).map_err(|e| fail(e.to_string())),
+            Conversion::Float => input.parse::<f64>().map(Value::Float).map_err(|e| fail(e.to_string())),
+            Conversion::Boolean => match input.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "y" => Ok(Value::Boolean(true)),
+                "false" | "0" | "no" | "n" => Ok(Value::Boolean(false)),
+                _ => Err(fail("not a recognized boolean".to_string())),
+            },
+            Conversion::TimestampFmt(fmt) => chrono::NaiveDateTime::parse_from_str(input, fmt)
+                .map_err(|e| fail(e.to_string()))
+                .and_then(|naive| match chrono::TimeZone::from_local_datetime(&chrono::Local, &naive) {
+                    chrono::LocalResult::Single(dt) => Ok(Value::Timestamp(dt.timestamp())),
+                    // DST "fall back": two local times map to the same wall
+                    // clock reading; take the earlier (pre-transition) one.
+                    chrono::LocalResult::Ambiguous(earliest, _latest) => Ok(Value::Timestamp(earliest.timestamp())),
+                    chrono::LocalResult::None => Err(fail("local time does not exist (DST gap)".to_string())),
+                }),
+            Conversion::TimestampTZFmt(fmt) => chrono::DateTime::parse_from_str(input, fmt)
+                .map(|dt| Value::Timestamp(dt.timestamp()))
+                .map_err(|e| fail(e.to_string())),
+            Conversion::Timestamp => {
+                if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(input) {
+                    return Ok(Value::Timestamp(dt.timestamp()));
+                }
+                for fmt in COMMON_TIMESTAMP_FORMATS {
+                    if let Ok(naive) = chrono::NaiveDateTime::parse_from_str(input, fmt) {
+                        return Ok(Value::Timestamp(naive.and_utc().timestamp()));
+                    }
+                }
+                Err(fail("no known timestamp format matched".to_string()))
+            }
+        }
+    }
+}
+
+fn demonstrate_conversions() {
+    let fields: Vec<(&str, &str)> = vec![
+        ("int", "42"),
+        ("float", "3.14"),
+        ("bool", "yes"),
+        ("bytes", "raw-value"),
+        ("timestamp", "2024-03-15T12:30:00Z"),
+        ("timestamp|%Y/%m/%d", "2024/03/15"),
+    ];
+
+    for (conversion_name, raw) in fields {
+        let conversion: Conversion = conversion_name.parse().expect("known conversion name");
+        match conversion.apply(raw) {
+            Ok(value) => println!("   '{}' as {} -> {:?}", raw, conversion_name, value),
+            Err(e) => println!("   '{}' as {} -> error: {}", raw, conversion_name, e),
+        }
+    }
+}
+
+// ============================================================================
+// 15. WEIGHTED, PROVENANCE-TRACKED RULE ENGINE OVER `Shape` FACTS
+// ============================================================================
+
+// A ground fact with a confidence weight in [0, 1].
+#[derive(Debug, Clone, PartialEq)]
+struct Fact {
+    predicate: String,
+    args: Vec<String>,
+    weight: f64,
+}
+
+// An atom in a rule's head or body. Args starting with '?' are variables;
+// everything else must match literally.
+#[derive(Debug, Clone)]
+struct RuleAtom {
+    predicate: String,
+    args: Vec<String>,
+}
+
+impl RuleAtom {
+    fn new(predicate: &str, args: &[&str]) -> Self {
+        RuleAtom {
+            predicate: predicate.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+// `head :- body1, body2, ...`
+#[derive(Debug, Clone)]
+struct Rule {
+    head: RuleAtom,
+    body: Vec<RuleAtom>,
+}
+
+fn unify_atom(
+    atom: &RuleAtom,
+    facts: &[Fact],
+    bindings: &HashMap<String, String>,
+) -> Vec<(HashMap<String, String>, f64)> {
+    let mut results = Vec::new();
+    for fact in facts
+        .iter()
+        .filter(|f| f.predicate == atom.predicate && f.args.len() == atom.args.len())
+    {
+        let mut candidate = bindings.clone();
+        let mut matched = true;
+        for (pattern_arg, fact_arg) in atom.args.iter().zip(fact.args.iter()) {
+            if let Some(var) = pattern_arg.strip_prefix('?') {
+                match candidate.get(var) {
+                    Some(bound) if bound != fact_arg => {
+                        matched = false;
+                        break;
+                    }
+                    Some(_) => {}
+                    None => {
+                        candidate.insert(var.to_string(), fact_arg.clone());
+                    }
+                }
+            } else if pattern_arg != fact_arg {
+                matched = false;
+                break;
+            }
+        }
+        if matched {
+            results.push((candidate, fact.weight));
+        }
+    }
+    results
+}
+
+// Conjunction: the weight of a rule firing is the product of its body facts'
+// weights, joined left-to-right across the bindings found so far.
+fn solve_body(body: &[RuleAtom], facts: &[Fact]) -> Vec<(HashMap<String, String>, f64)> {
+    let mut solutions: Vec<(HashMap<String, String>, f64)> = vec![(HashMap::new(), 1.0)];
+    for atom in body {
+        let mut next = Vec::new();
+        for (bindings, weight_so_far) in &solutions {
+            for (candidate, fact_weight) in unify_atom(atom, facts, bindings) {
+                next.push((candidate, weight_so_far * fact_weight));
+            }
+        }
+        solutions = next;
+    }
+    solutions
+}
+
+fn instantiate(atom: &RuleAtom, bindings: &HashMap<String, String>, weight: f64) -> Fact {
+    let args = atom
+        .args
+        .iter()
+        .map(|a| match a.strip_prefix('?') {
+            Some(var) => bindings.get(var).cloned().unwrap_or_else(|| a.clone()),
+            None => a.clone(),
+        })
+        .collect();
+    Fact {
+        predicate: atom.predicate.clone(),
+        args,
+        weight,
+    }
+}
+
+// Small Datalog-style engine: facts carry confidence, rules derive new facts
+// bottom-up, and a head reached by more than one rule instance combines its
+// weights with noisy-or instead of overwriting.
+struct RuleEngine {
+    facts: Vec<Fact>,
+    rules: Vec<Rule>,
+}
+
+impl RuleEngine {
+    fn new() -> Self {
+        RuleEngine {
+            facts: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    fn assert_fact(&mut self, fact: Fact) {
+        self.merge_fact(fact);
+    }
+
+    fn add_rule(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    // Probabilistic disjunction (noisy-or): combining two independent paths
+    // to the same fact should raise confidence, never just replace it.
+    fn merge_fact(&mut self, fact: Fact) -> f64 {
+        if let Some(existing) = self
+            .facts
+            .iter_mut()
+            .find(|f| f.predicate == fact.predicate && f.args == fact.args)
+        {
+            let combined = 1.0 - (1.0 - existing.weight) * (1.0 - fact.weight);
+            let delta = (combined - existing.weight).abs();
+            existing.weight = combined;
+            delta
+        } else {
+            self.facts.push(fact);
+            1.0
+        }
+    }
+
+    // Overwrites (rather than noisy-or merges) a derived fact's weight with
+    // the full combination of this iteration's derivations, so re-firing a
+    // rule whose bindings haven't changed doesn't compound its contribution
+    // against itself on every pass.
+    fn set_derived_fact(&mut self, predicate: String, args: Vec<String>, weight: f64) -> f64 {
+        if let Some(existing) = self.facts.iter_mut().find(|f| f.predicate == predicate && f.args == args) {
+            let delta = (weight - existing.weight).abs();
+            existing.weight = weight;
+            delta
+        } else {
+            self.facts.push(Fact { predicate, args, weight });
+            weight
+        }
+    }
+
+    // Naive bottom-up fixpoint: re-fire every rule against all facts on each
+    // pass (not only against facts that changed last pass) until no fact's
+    // weight moves by more than `epsilon`, capped so a malformed rule set
+    // can't loop forever.
+    fn run_to_fixpoint(&mut self, epsilon: f64) {
+        const MAX_ITERATIONS: u32 = 100;
+        for _ in 0..MAX_ITERATIONS {
+            let mut max_delta = 0.0_f64;
+            let rules = self.rules.clone();
+
+            // Combine every rule's derivation of a given head exactly once
+            // per pass, instead of merging each one straight into the head
+            // fact's already-accumulated weight.
+            let mut derived: HashMap<(String, Vec<String>), f64> = HashMap::new();
+            for rule in &rules {
+                for (bindings, derived_weight) in solve_body(&rule.body, &self.facts) {
+                    let head_fact = instantiate(&rule.head, &bindings, derived_weight);
+                    let combined = derived.entry((head_fact.predicate, head_fact.args)).or_insert(0.0);
+                    *combined = 1.0 - (1.0 - *combined) * (1.0 - head_fact.weight);
+                }
+            }
+            for ((predicate, args), weight) in derived {
+                let delta = self.set_derived_fact(predicate, args, weight);
+                max_delta = max_delta.max(delta);
+            }
+
+            if max_delta < epsilon {
+                break;
+            }
+        }
+    }
+
+    // Top-k highest-confidence facts for a predicate.
+    fn query(&self, predicate: &str, top_k: usize) -> Vec<&Fact> {
+        let mut matches: Vec<&Fact> = self.facts.iter().filter(|f| f.predicate == predicate).collect();
+        matches.sort_by(|a, b| b.weight.partial_cmp(&a.weight).unwrap());
+        matches.truncate(top_k);
+        matches
+    }
+}
+
+fn demonstrate_rule_engine() {
+    let shapes = vec![
+        Shape::Rectangle { width: 10.0, height: 8.0 },
+        Shape::Rectangle { width: 1.0, height: 1.0 },
+        Shape::Circle { radius: 5.0 },
+    ];
+
+    let mut engine = RuleEngine::new();
+    let area_threshold = 20.0;
+
+    for (id, shape) in shapes.iter().enumerate() {
+        let id = id.to_string();
+        if matches!(shape, Shape::Rectangle { .. }) {
+            engine.assert_fact(Fact {
+                predicate: "is_rectangle".to_string(),
+                args: vec![id.clone()],
+                weight: 1.0,
+            });
+        }
+        // Confidence grows with the area up to the threshold (full
+        // confidence at or above it), rather than a hard cutoff.
+        let area_confidence = (shape.area() / area_threshold).min(1.0);
+        if area_confidence > 0.0 {
+            engine.assert_fact(Fact {
+                predicate: "large_area".to_string(),
+                args: vec![id.clone()],
+                weight: area_confidence,
+            });
+        }
+        // An independent signal for "large": the larger of width and height,
+        // measured against 6 (full confidence at 6 or more).
+        let wide_confidence = match shape {
+            Shape::Rectangle { width, height } => (width.max(*height) / 6.0).min(1.0),
+            _ => 0.0,
+        };
+        if wide_confidence > 0.0 {
+            engine.assert_fact(Fact {
+                predicate: "wide_shape".to_string(),
+                args: vec![id],
+                weight: wide_confidence,
+            });
+        }
+    }
+
+    // Two independent rules reach the same head, so a shape that is both
+    // "large by area" and "wide" gets its confidence combined via noisy-or.
+    engine.add_rule(Rule {
+        head: RuleAtom::new("probably_large_rectangle", &["?x"]),
+        body: vec![RuleAtom::new("large_area", &["?x"]), RuleAtom::new("is_rectangle", &["?x"])],
+    });
+    engine.add_rule(Rule {
+        head: RuleAtom::new("probably_large_rectangle", &["?x"]),
+        body: vec![RuleAtom::new("wide_shape", &["?x"]), RuleAtom::new("is_rectangle", &["?x"])],
+    });
+
+    engine.run_to_fixpoint(1e-6);
+
+    println!("   Which shapes are probably large rectangles?");
+    for fact in engine.query("probably_large_rectangle", 3) {
+        println!("      shape {} -> confidence {:.3}", fact.args[0], fact.weight);
+    }
 }
 
 // ============================================================================
@@ -535,8 +1117,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Ok(response) => println!("   Topic '{}': {}", topic, response),
             Err(e) => println!("   Error asking about '{}': {}", topic, e),
         }
+        // Fire-and-forget notification via the non-blocking half of the client.
+        if let Err(e) = notify_llm_about_rust(topic).await {
+            println!("   Async notify error for '{}': {}", topic, e);
+        }
     }
-    
+    println!();
+
+    // 14. Conversion Subsystem
+    println!("14. 🔀 Conversion Subsystem");
+    demonstrate_conversions();
+    println!();
+
+    // 15. Rule Engine
+    println!("15. 🧠 Weighted Rule Engine");
+    demonstrate_rule_engine();
+
     println!("\n🎉 Demo completed successfully!");
     Ok(())
 }
@@ -647,4 +1243,131 @@ mod tests {
         let container = Container::new(42);
         assert_eq!(*container.get(), 42);
     }
+
+    #[test]
+    fn test_is_transient_status() {
+        assert!(is_transient_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient_status(reqwest::StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_transient_status(reqwest::StatusCode::BAD_REQUEST));
+        assert!(!is_transient_status(reqwest::StatusCode::OK));
+    }
+
+    #[test]
+    fn test_conversion_from_str() {
+        assert_eq!("int".parse::<Conversion>().unwrap(), Conversion::Integer);
+        assert_eq!("integer".parse::<Conversion>().unwrap(), Conversion::Integer);
+        assert_eq!("float".parse::<Conversion>().unwrap(), Conversion::Float);
+        assert_eq!("bool".parse::<Conversion>().unwrap(), Conversion::Boolean);
+        assert_eq!("asis".parse::<Conversion>().unwrap(), Conversion::Bytes);
+        assert_eq!("timestamp".parse::<Conversion>().unwrap(), Conversion::Timestamp);
+        assert_eq!(
+            "timestamp|%Y-%m-%d".parse::<Conversion>().unwrap(),
+            Conversion::TimestampFmt("%Y-%m-%d".to_string())
+        );
+        assert_eq!(
+            "timestamp|%Y-%m-%dT%H:%M:%S%:z".parse::<Conversion>().unwrap(),
+            Conversion::TimestampTZFmt("%Y-%m-%dT%H:%M:%S%:z".to_string())
+        );
+        assert!(matches!(
+            "nonsense".parse::<Conversion>(),
+            Err(ConversionError::UnknownConversion { name }) if name == "nonsense"
+        ));
+    }
+
+    #[test]
+    fn test_conversion_apply_each_variant() {
+        assert_eq!(Conversion::Integer.apply("42").unwrap(), Value::Integer(42));
+        assert_eq!(Conversion::Float.apply("3.5").unwrap(), Value::Float(3.5));
+        assert_eq!(Conversion::Boolean.apply("yes").unwrap(), Value::Boolean(true));
+        assert_eq!(Conversion::Boolean.apply("0").unwrap(), Value::Boolean(false));
+        assert_eq!(Conversion::Bytes.apply("raw").unwrap(), Value::Bytes("raw".to_string()));
+        assert!(Conversion::Integer.apply("not-a-number").is_err());
+    }
+
+    #[test]
+    fn test_timestamp_rfc3339_round_trip() {
+        let value = Conversion::Timestamp.apply("2024-03-15T12:30:00Z").unwrap();
+        assert_eq!(value, Value::Timestamp(1710505800));
+
+        // Render the epoch back to RFC3339 and parse it again.
+        let rendered = chrono::DateTime::from_timestamp(1710505800, 0).unwrap().to_rfc3339();
+        assert_eq!(Conversion::Timestamp.apply(&rendered).unwrap(), value);
+    }
+
+    #[test]
+    fn test_timestamp_fmt_interprets_input_in_local_time() {
+        // Pin a concrete, known-offset timezone and a hardcoded expected
+        // epoch instead of re-deriving "expected" from the same
+        // `chrono::Local` call the code under test makes: on a UTC host
+        // (this sandbox's default, and most CI) local time equals UTC, so
+        // that comparison can't tell this fix apart from the `.and_utc()`
+        // bug it was written to catch.
+        //
+        // SAFETY: this test runs single-threaded w.r.t. this env var; no
+        // other test reads or writes `TZ`.
+        unsafe {
+            std::env::set_var("TZ", "America/New_York");
+        }
+
+        // January: plain EST (UTC-5), no DST transition nearby to worry about.
+        let value = Conversion::TimestampFmt("%Y-%m-%d %H:%M:%S".to_string())
+            .apply("2024-01-15 12:30:00")
+            .unwrap();
+
+        // 2024-01-15T12:30:00-05:00 == 2024-01-15T17:30:00Z
+        assert_eq!(value, Value::Timestamp(1705339800));
+    }
+
+    #[test]
+    fn test_timestamp_tz_fmt_apply() {
+        let value = Conversion::TimestampTZFmt("%Y-%m-%dT%H:%M:%S%:z".to_string())
+            .apply("2024-03-15T12:30:00+02:00")
+            .unwrap();
+        assert_eq!(value, Value::Timestamp(1710498600));
+    }
+
+    #[test]
+    fn test_rule_engine_noisy_or_combination() {
+        let mut engine = RuleEngine::new();
+        engine.assert_fact(Fact { predicate: "a".to_string(), args: vec!["x".to_string()], weight: 0.5 });
+        engine.assert_fact(Fact { predicate: "b".to_string(), args: vec!["x".to_string()], weight: 0.5 });
+        // Two independent rules derive the same head from different evidence.
+        engine.add_rule(Rule {
+            head: RuleAtom::new("derived", &["?v"]),
+            body: vec![RuleAtom::new("a", &["?v"])],
+        });
+        engine.add_rule(Rule {
+            head: RuleAtom::new("derived", &["?v"]),
+            body: vec![RuleAtom::new("b", &["?v"])],
+        });
+
+        engine.run_to_fixpoint(1e-9);
+
+        let results = engine.query("derived", 1);
+        assert_eq!(results.len(), 1);
+        // noisy-or: 1 - (1 - 0.5)(1 - 0.5) = 0.75
+        assert!((results[0].weight - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rule_engine_monotone_convergence() {
+        let mut engine = RuleEngine::new();
+        engine.assert_fact(Fact { predicate: "seed".to_string(), args: vec!["x".to_string()], weight: 0.9 });
+        engine.add_rule(Rule {
+            head: RuleAtom::new("derived", &["?v"]),
+            body: vec![RuleAtom::new("seed", &["?v"])],
+        });
+
+        engine.run_to_fixpoint(1e-9);
+        let first_pass = engine.query("derived", 1)[0].weight;
+
+        // Running to fixpoint again must not lower the weight, nor compound
+        // the derived fact with itself past what the first run settled on.
+        engine.run_to_fixpoint(1e-9);
+        let second_pass = engine.query("derived", 1)[0].weight;
+
+        assert!(second_pass >= first_pass);
+        assert!(second_pass <= first_pass + 1e-9);
+        assert!(second_pass <= 1.0);
+    }
 }